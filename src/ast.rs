@@ -0,0 +1,45 @@
+use crate::{lexer::Span, symbol_table::Qualifier};
+use std::collections::HashMap;
+
+/// A single parsed source file's top-level declarations.
+#[derive(Clone, Debug, Default)]
+pub struct Module {
+  pub functions: Vec<Function>,
+}
+
+/// All the modules that make up a compilation, keyed by their qualifier.
+pub type Package = HashMap<Qualifier, Module>;
+
+#[derive(Clone, Debug)]
+pub struct Function {
+  pub name: String,
+  pub parameters: Vec<Parameter>,
+  pub body: Block,
+  pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub struct Parameter {
+  pub name: String,
+  pub span: Span,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Block {
+  pub statements: Vec<Node>,
+}
+
+#[derive(Clone, Debug)]
+pub enum Node {
+  Binding { name: String, value: Box<Node>, span: Span },
+  Literal { span: Span },
+}
+
+impl Node {
+  pub fn span(&self) -> Span {
+    match self {
+      Node::Binding { span, .. } => *span,
+      Node::Literal { span } => *span,
+    }
+  }
+}
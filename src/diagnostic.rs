@@ -0,0 +1,51 @@
+use crate::lexer::Span;
+
+/// The result type produced by every compiler phase: either the phase's
+/// output, or the list of diagnostics that prevented it from producing one.
+pub type Maybe<T> = Result<T, Vec<Diagnostic>>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+  Warning,
+  Error,
+}
+
+/// A single diagnostic message anchored to the primary span that caused it.
+///
+/// The primary span is what annotation-matching and IDE integrations key
+/// off of; a diagnostic may eventually grow secondary/related spans, but
+/// only the primary one is required to report a location.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+  pub message: String,
+  pub severity: Severity,
+  pub primary_span: Span,
+}
+
+impl Diagnostic {
+  pub fn new(message: impl Into<String>, severity: Severity, primary_span: Span) -> Self {
+    Self {
+      message: message.into(),
+      severity,
+      primary_span,
+    }
+  }
+
+  /// The 1-indexed source line this diagnostic's primary span starts on.
+  pub fn primary_line(&self) -> usize {
+    self.primary_span.line
+  }
+}
+
+pub struct DiagnosticsHelper {
+  pub diagnostics: Vec<Diagnostic>,
+}
+
+impl DiagnosticsHelper {
+  pub fn contains_errors(&self) -> bool {
+    self
+      .diagnostics
+      .iter()
+      .any(|diagnostic| diagnostic.severity == Severity::Error)
+  }
+}
@@ -0,0 +1,85 @@
+use crate::diagnostic;
+
+/// A half-open byte-offset range into the source file a token or AST node
+/// was produced from, paired with the 1-indexed line it starts on.
+///
+/// Lines are tracked alongside byte offsets so that diagnostics and the
+/// debug-info backend can report a human-readable location without having
+/// to re-scan the source text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Span {
+  pub start: usize,
+  pub end: usize,
+  pub line: usize,
+}
+
+impl Span {
+  pub fn new(start: usize, end: usize, line: usize) -> Self {
+    Self { start, end, line }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+  Whitespace(char),
+  Comment(String),
+  Identifier(String),
+  Int(i64),
+  // REVISE: Flesh out the remaining literal and punctuation kinds as the
+  // language grows; only what the existing tests exercise is modeled here.
+  Illegal(char),
+}
+
+/// A lexed token together with the span it occupies in the source file.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Token(pub TokenKind, pub Span);
+
+pub struct Lexer<'a> {
+  source_code: &'a str,
+  index: usize,
+  line: usize,
+}
+
+impl<'a> Lexer<'a> {
+  pub fn new(source_code: &'a str) -> Self {
+    Self {
+      source_code,
+      index: 0,
+      line: 1,
+    }
+  }
+
+  pub fn lex_all(source_code: &'a str) -> diagnostic::Maybe<Vec<Token>> {
+    let mut lexer = Self::new(source_code);
+    let mut tokens = Vec::new();
+
+    while let Some(token) = lexer.next_token() {
+      tokens.push(token);
+    }
+
+    Ok(tokens)
+  }
+
+  fn next_token(&mut self) -> Option<Token> {
+    let character = self.source_code[self.index..].chars().next()?;
+    let start = self.index;
+
+    let line = self.line;
+
+    self.index += character.len_utf8();
+
+    let kind = if character == '\n' {
+      self.line += 1;
+
+      TokenKind::Whitespace(character)
+    } else if character.is_whitespace() {
+      TokenKind::Whitespace(character)
+    } else if character.is_alphabetic() {
+      TokenKind::Identifier(character.to_string())
+    } else {
+      TokenKind::Illegal(character)
+    };
+
+    Some(Token(kind, Span::new(start, self.index, line)))
+  }
+}
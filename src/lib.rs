@@ -0,0 +1,21 @@
+//! `tails` is a small, work-in-progress compiler.
+//!
+//! Scope note: `ast`, `lexer`, `parser`, `symbol_table`, `diagnostic`, and
+//! `pass` are currently minimal scaffolding — a real-enough skeleton for the
+//! pipeline's shape (lex, parse, resolve, infer, lower) to exist and be
+//! exercised by `tests/codegen_tests.rs` and `testing::run`, not a finished
+//! front end. The lexer only tokenizes identifiers/whitespace/illegal
+//! characters, the parser produces an empty `Module` (or an error when the
+//! first token is illegal), and passes
+//! like `resolution`/`inference` are no-ops. Each of those grows into a real
+//! implementation as its own backlog item lands; treat call sites against
+//! them as the intended public shape, not evidence the behavior behind them
+//! is complete.
+
+pub mod ast;
+pub mod diagnostic;
+pub mod lexer;
+pub mod parser;
+pub mod pass;
+pub mod symbol_table;
+pub mod testing;
@@ -0,0 +1,58 @@
+use crate::{
+  ast,
+  diagnostic::{self, Diagnostic, Severity},
+  lexer::Token,
+  symbol_table::Qualifier,
+};
+
+/// Recursive-descent parser over a filtered token stream.
+///
+/// The parser hands out monotonically increasing ids via `next_id`, and
+/// `get_id_count` reports how many were handed out; `testing::run` passes
+/// that count on to `PassManager::run`.
+pub struct Parser {
+  tokens: Vec<Token>,
+  index: usize,
+  id_counter: usize,
+}
+
+impl Parser {
+  pub fn new(tokens: Vec<Token>) -> Self {
+    Self {
+      tokens,
+      index: 0,
+      id_counter: 0,
+    }
+  }
+
+  pub fn get_id_count(&self) -> usize {
+    self.id_counter
+  }
+
+  fn next_id(&mut self) -> usize {
+    let id = self.id_counter;
+
+    self.id_counter += 1;
+
+    id
+  }
+
+  pub fn parse_module(&mut self, _qualifier: Qualifier) -> diagnostic::Maybe<ast::Module> {
+    if let Some(token) = self.tokens.get(self.index) {
+      if matches!(token.0, crate::lexer::TokenKind::Illegal(_)) {
+        return Err(vec![Diagnostic::new(
+          "encountered an illegal token while parsing a module",
+          Severity::Error,
+          token.1,
+        )]);
+      }
+    }
+
+    // REVISE: This is a placeholder traversal; the real grammar for
+    // functions, bindings, and expressions is parsed here once the
+    // corresponding AST nodes exist.
+    let _ = self.next_id();
+
+    Ok(ast::Module::default())
+  }
+}
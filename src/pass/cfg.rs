@@ -0,0 +1,86 @@
+/// Identifies a basic block within a single function's control-flow graph.
+pub type BlockId = usize;
+
+/// Identifies an SSA value; two guards are only considered equivalent if
+/// they share the same operand id (no aliasing reasoning is performed).
+pub type ValueId = usize;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GuardKind {
+  DivisionByZero,
+  NullDereference,
+  Memo,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GuardCheck {
+  pub kind: GuardKind,
+  pub operand: ValueId,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct BasicBlock {
+  pub predecessors: Vec<BlockId>,
+  pub successors: Vec<BlockId>,
+  pub guards: Vec<GuardCheck>,
+}
+
+/// A function's control-flow graph, indexed by `BlockId`. Block `0` is
+/// always the entry block.
+#[derive(Clone, Debug, Default)]
+pub struct Cfg {
+  pub blocks: Vec<BasicBlock>,
+}
+
+/// Builds one `Cfg` per function in `package`.
+///
+/// REVISE: `ast::Node` does not yet have an `If`/branch variant, so every
+/// function currently lowers to a single block with no guard checks; this
+/// is where per-branch blocks and `GuardCheck`s get threaded in once those
+/// AST nodes exist. Building one real (if trivial) block per function now,
+/// rather than returning no graphs at all, keeps `RedundantGuardElimination`
+/// reachable from real compilations instead of only from this file's own
+/// unit tests.
+pub fn build_cfgs_for_package(package: &crate::ast::Package) -> Vec<Cfg> {
+  package
+    .values()
+    .flat_map(|module| &module.functions)
+    .map(|_function| Cfg {
+      blocks: vec![BasicBlock::default()],
+    })
+    .collect()
+}
+
+impl Cfg {
+  pub const ENTRY: BlockId = 0;
+
+  pub fn block_count(&self) -> usize {
+    self.blocks.len()
+  }
+
+  /// Visits blocks reachable from the entry block in reverse-postorder;
+  /// unreachable blocks are skipped, matching the dominator computation.
+  pub fn reverse_postorder(&self) -> Vec<BlockId> {
+    let mut visited = vec![false; self.blocks.len()];
+    let mut postorder = Vec::with_capacity(self.blocks.len());
+
+    self.visit_postorder(Self::ENTRY, &mut visited, &mut postorder);
+    postorder.reverse();
+
+    postorder
+  }
+
+  fn visit_postorder(&self, block: BlockId, visited: &mut Vec<bool>, postorder: &mut Vec<BlockId>) {
+    if visited[block] {
+      return;
+    }
+
+    visited[block] = true;
+
+    for successor in &self.blocks[block].successors {
+      self.visit_postorder(*successor, visited, postorder);
+    }
+
+    postorder.push(block);
+  }
+}
@@ -0,0 +1,97 @@
+//! Source-based coverage instrumentation, mirroring rustc's coverage-map
+//! split: this pass only *records* which source region each counter
+//! belongs to (the "coverage map"); `llvm_lowering` is what actually
+//! inserts the `llvm.instrprof.increment` counter bumps and emits the
+//! `__llvm_covmap`-style section an external profiler reads at runtime.
+
+use crate::{ast::Package, lexer::Span};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CoverageRegion {
+  pub span: Span,
+  pub counter_index: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FunctionCoverage {
+  pub function_name: String,
+  pub regions: Vec<CoverageRegion>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CoverageMap {
+  pub functions: Vec<FunctionCoverage>,
+}
+
+/// Associates a dense, per-function-unique counter index with every region
+/// that should be instrumented: the function's entry, plus every
+/// conditional-branch region within it.
+///
+/// `ast::Node` does not yet have an `If`/branch variant (see
+/// `collect_branch_regions`), so today every function's coverage map only
+/// ever contains its entry region; branch regions start showing up once
+/// that AST support lands.
+pub fn instrument_package(package: &Package) -> CoverageMap {
+  let mut functions = Vec::new();
+
+  for module in package.values() {
+    for function in &module.functions {
+      functions.push(FunctionCoverage {
+        function_name: function.name.clone(),
+        regions: instrument_function_regions(function),
+      });
+    }
+  }
+
+  CoverageMap { functions }
+}
+
+fn instrument_function_regions(function: &crate::ast::Function) -> Vec<CoverageRegion> {
+  let mut counter_index = 0;
+  let mut regions = vec![CoverageRegion {
+    span: function.span,
+    counter_index,
+  }];
+
+  counter_index += 1;
+
+  for node in &function.body.statements {
+    collect_branch_regions(node, &mut counter_index, &mut regions);
+  }
+
+  regions
+}
+
+/// Walks a node looking for conditional-branch regions, assigning each one
+/// the next dense counter index.
+///
+/// REVISE: `ast::Node` does not yet have an `If`/branch variant, so no
+/// regions are found beyond the function entry; this is where each branch
+/// arm's span gets its own counter once conditionals are modeled.
+fn collect_branch_regions(_node: &crate::ast::Node, _counter_index: &mut u32, _regions: &mut Vec<CoverageRegion>) {}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::ast::{Block, Function, Node};
+
+  #[test]
+  fn only_the_entry_region_is_instrumented_until_branches_are_modeled() {
+    let function = Function {
+      name: String::from("f"),
+      parameters: Vec::new(),
+      body: Block {
+        statements: vec![Node::Literal { span: Span::new(0, 0, 1) }],
+      },
+      span: Span::new(0, 0, 1),
+    };
+
+    let regions = instrument_function_regions(&function);
+
+    assert_eq!(
+      regions.len(),
+      1,
+      "branch regions aren't collected until ast::Node models conditionals; pin this so the gap is visible, not silently papered over"
+    );
+  }
+}
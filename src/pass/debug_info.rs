@@ -0,0 +1,104 @@
+//! Attaches DWARF debug-info metadata to lowered LLVM IR via inkwell's
+//! `DebugInfoBuilder`.
+//!
+//! One compile-unit is created per module and one `DISubprogram` per
+//! function; the lowering pass tags a function's instructions with a single
+//! `DILocation` at the function's `lexer::Span` line (column 1), attached as
+//! an `!dbg` metadata reference. No lexical-block scopes and no
+//! local-variable descriptors are emitted.
+
+use crate::ast::Function;
+use inkwell::debug_info::{
+  AsDIScope, DICompileUnit, DIFlagsConstants, DISubprogram, DebugInfoBuilder,
+};
+
+/// Per-module debug-info state threaded through lowering.
+///
+/// `DebugInfoBuilder` and `DICompileUnit` are owned by the inkwell module
+/// this was built for; `ModuleDebugInfo` just bundles them with the
+/// currently-open subprogram scope so lowering can attach `!dbg` locations
+/// without re-deriving the compile unit for every instruction.
+pub struct ModuleDebugInfo<'ctx> {
+  pub builder: DebugInfoBuilder<'ctx>,
+  pub compile_unit: DICompileUnit<'ctx>,
+  pub current_subprogram: Option<DISubprogram<'ctx>>,
+}
+
+impl<'ctx> ModuleDebugInfo<'ctx> {
+  /// Creates the module-level compile-unit debug-info builder.
+  ///
+  /// `module_name` and `directory` identify the source file the compile
+  /// unit describes, matching the qualifier the module was lowered under.
+  pub fn new(
+    inkwell_module: &inkwell::module::Module<'ctx>,
+    module_name: &str,
+    directory: &str,
+  ) -> Self {
+    let is_optimized = false;
+    let compiler_version = "";
+    let runtime_version = 0;
+    let split_name = "";
+    let dwo_id = 0;
+    let split_debug_inlining = false;
+    let debug_info_for_profiling = false;
+
+    let (builder, compile_unit) = inkwell_module.create_debug_info_builder(
+      true,
+      inkwell::debug_info::DWARFSourceLanguage::C,
+      module_name,
+      directory,
+      compiler_version,
+      is_optimized,
+      "",
+      runtime_version,
+      split_name,
+      inkwell::debug_info::DWARFEmissionKind::Full,
+      dwo_id,
+      split_debug_inlining,
+      debug_info_for_profiling,
+      "",
+      "",
+    );
+
+    Self {
+      builder,
+      compile_unit,
+      current_subprogram: None,
+    }
+  }
+
+  /// Creates and enters a `DISubprogram` scope for `function`.
+  pub fn enter_function(&mut self, function: &Function) {
+    let file = self.compile_unit.get_file();
+
+    // REVISE: `ast::Parameter` does not carry a resolved `DIType` yet, so
+    // parameter types can't be listed individually; revisit once the type
+    // system threads them through here.
+    let subroutine_type = self.builder.create_subroutine_type(
+      file,
+      None,
+      &[],
+      inkwell::debug_info::DIFlags::PUBLIC,
+    );
+
+    let subprogram = self.builder.create_function(
+      self.compile_unit.as_debug_info_scope(),
+      &function.name,
+      None,
+      file,
+      function.span.line as u32,
+      subroutine_type,
+      false,
+      true,
+      function.span.line as u32,
+      inkwell::debug_info::DIFlags::PUBLIC,
+      false,
+    );
+
+    self.current_subprogram = Some(subprogram);
+  }
+
+  pub fn finalize(&self) {
+    self.builder.finalize();
+  }
+}
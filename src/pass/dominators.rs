@@ -0,0 +1,228 @@
+//! Lengauer-Tarjan dominator tree computation over a `Cfg`.
+//!
+//! This is the classic two-phase algorithm: a DFS numbers reachable blocks
+//! and records, for each block, the minimum semidominator found by walking
+//! predecessors through a path-compressing `eval`/`link` forest; blocks are
+//! then bucketed by their semidominator so that immediate dominators can be
+//! derived in a second pass once the semidominator of an ancestor is known.
+
+use super::cfg::{BlockId, Cfg};
+use std::collections::HashMap;
+
+/// Maps every block reachable from the entry block to its immediate
+/// dominator. The entry block has no immediate dominator and is omitted;
+/// unreachable blocks are also omitted.
+pub fn compute_immediate_dominators(cfg: &Cfg) -> HashMap<BlockId, BlockId> {
+  let n = cfg.block_count();
+
+  if n == 0 {
+    return HashMap::new();
+  }
+
+  // `dfn[v]` is `v`'s preorder DFS number, or `usize::MAX` if unreached.
+  let mut dfn = vec![usize::MAX; n];
+  let mut vertex = Vec::with_capacity(n);
+  let mut parent = vec![None; n];
+
+  depth_first_search(cfg, Cfg::ENTRY, &mut dfn, &mut vertex, &mut parent);
+
+  let reachable_count = vertex.len();
+  let mut semi: Vec<usize> = (0..n).collect();
+  let mut idom = vec![None; n];
+  let mut ancestor: Vec<Option<BlockId>> = vec![None; n];
+  let mut label: Vec<BlockId> = (0..n).collect();
+  let mut bucket: Vec<Vec<BlockId>> = vec![Vec::new(); n];
+
+  // Process blocks in decreasing DFS-number order, skipping the entry
+  // block (DFS number 0), which has no semidominator.
+  for i in (1..reachable_count).rev() {
+    let w = vertex[i];
+
+    for &v in &cfg.blocks[w].predecessors {
+      if dfn[v] == usize::MAX {
+        // `v` is unreachable from the entry block; skip it.
+        continue;
+      }
+
+      let u = eval(v, &dfn, &semi, &mut ancestor, &mut label);
+
+      if dfn[semi[u]] < dfn[semi[w]] {
+        semi[w] = semi[u];
+      }
+    }
+
+    bucket[semi[w]].push(w);
+    link(parent[w].expect("non-entry block should have a DFS parent"), w, &mut ancestor);
+
+    let parent_of_w = parent[w].expect("non-entry block should have a DFS parent");
+    let bucketed = std::mem::take(&mut bucket[parent_of_w]);
+
+    for v in bucketed {
+      let u = eval(v, &dfn, &semi, &mut ancestor, &mut label);
+
+      idom[v] = Some(if dfn[semi[u]] < dfn[semi[v]] {
+        u
+      } else {
+        parent_of_w
+      });
+    }
+  }
+
+  for i in 1..reachable_count {
+    let w = vertex[i];
+
+    if idom[w] != Some(semi[w]) {
+      idom[w] = idom[idom[w].expect("idom should have been assigned in the first pass")];
+    }
+  }
+
+  (1..reachable_count)
+    .map(|i| vertex[i])
+    .map(|w| (w, idom[w].expect("every reachable non-entry block should have an idom")))
+    .collect()
+}
+
+fn depth_first_search(
+  cfg: &Cfg,
+  block: BlockId,
+  dfn: &mut [usize],
+  vertex: &mut Vec<BlockId>,
+  parent: &mut [Option<BlockId>],
+) {
+  if dfn[block] != usize::MAX {
+    return;
+  }
+
+  dfn[block] = vertex.len();
+  vertex.push(block);
+
+  for &successor in &cfg.blocks[block].successors {
+    if dfn[successor] == usize::MAX {
+      parent[successor] = Some(block);
+      depth_first_search(cfg, successor, dfn, vertex, parent);
+    }
+  }
+}
+
+/// Returns the block with the minimum-`dfn` semidominator on the path from
+/// `v` to the root of its ancestor tree, compressing the path as it goes.
+fn eval(
+  v: BlockId,
+  dfn: &[usize],
+  semi: &[usize],
+  ancestor: &mut [Option<BlockId>],
+  label: &mut [BlockId],
+) -> BlockId {
+  if ancestor[v].is_none() {
+    return label[v];
+  }
+
+  compress(v, dfn, semi, ancestor, label);
+
+  label[v]
+}
+
+fn compress(
+  v: BlockId,
+  dfn: &[usize],
+  semi: &[usize],
+  ancestor: &mut [Option<BlockId>],
+  label: &mut [BlockId],
+) {
+  let a = ancestor[v].expect("compress should only be called on linked blocks");
+
+  if ancestor[a].is_none() {
+    return;
+  }
+
+  compress(a, dfn, semi, ancestor, label);
+
+  if dfn[semi[label[a]]] < dfn[semi[label[v]]] {
+    label[v] = label[a];
+  }
+
+  ancestor[v] = ancestor[a];
+}
+
+fn link(parent: BlockId, child: BlockId, ancestor: &mut [Option<BlockId>]) {
+  ancestor[child] = Some(parent);
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::pass::cfg::BasicBlock;
+
+  fn block(predecessors: &[BlockId], successors: &[BlockId]) -> BasicBlock {
+    BasicBlock {
+      predecessors: predecessors.to_vec(),
+      successors: successors.to_vec(),
+      guards: Vec::new(),
+    }
+  }
+
+  #[test]
+  fn diamond_cfg_dominators() {
+    // 0 -> 1, 0 -> 2, 1 -> 3, 2 -> 3
+    let cfg = Cfg {
+      blocks: vec![
+        block(&[], &[1, 2]),
+        block(&[0], &[3]),
+        block(&[0], &[3]),
+        block(&[1, 2], &[]),
+      ],
+    };
+
+    let idom = compute_immediate_dominators(&cfg);
+
+    assert_eq!(idom.get(&1), Some(&0));
+    assert_eq!(idom.get(&2), Some(&0));
+    assert_eq!(idom.get(&3), Some(&0));
+    assert_eq!(idom.get(&0), None, "the entry block should have no dominator");
+  }
+
+  #[test]
+  fn dominators_are_correct_when_block_ids_are_scrambled_relative_to_dfs_order() {
+    // Diamond-like shape (entry splits into two paths of unequal length
+    // that rejoin before a tail block), with block ids assigned so that DFS
+    // preorder visits them in a different order than their numeric order:
+    // DFS visits 0, 5, 1, 4, 2, then backtracks to visit 3.
+    let cfg = Cfg {
+      blocks: vec![
+        block(&[], &[5, 3]),  // 0: entry
+        block(&[5], &[4]),    // 1
+        block(&[4], &[]),     // 2
+        block(&[0], &[4]),    // 3
+        block(&[1, 3], &[2]), // 4
+        block(&[0], &[1]),    // 5
+      ],
+    };
+
+    let idom = compute_immediate_dominators(&cfg);
+
+    assert_eq!(idom.get(&5), Some(&0));
+    assert_eq!(idom.get(&3), Some(&0));
+    assert_eq!(idom.get(&1), Some(&5));
+    assert_eq!(
+      idom.get(&4),
+      Some(&0),
+      "block 4 is reachable via two disjoint paths from the entry, so the entry is its idom"
+    );
+    assert_eq!(idom.get(&2), Some(&4));
+    assert_eq!(idom.get(&0), None, "the entry block should have no dominator");
+  }
+
+  #[test]
+  fn unreachable_block_is_skipped() {
+    // 0 -> 1; block 2 has no path from the entry block.
+    let cfg = Cfg {
+      blocks: vec![block(&[], &[1]), block(&[0], &[]), block(&[], &[])],
+    };
+
+    let idom = compute_immediate_dominators(&cfg);
+
+    assert_eq!(idom.get(&1), Some(&0));
+    assert_eq!(idom.get(&2), None, "an unreachable block should not get a dominator");
+    assert_eq!(idom.len(), 1);
+  }
+}
@@ -0,0 +1,169 @@
+use super::{coverage::CoverageMap, debug_info::ModuleDebugInfo};
+use crate::{ast::Package, diagnostic::Diagnostic};
+use inkwell::{context::Context, debug_info::AsDIScope, AddressSpace};
+
+const COVERAGE_MAP_SECTION: &str = "__llvm_covmap";
+
+/// Lowers a fully-resolved, type-checked `Package` into LLVM IR text.
+///
+/// `_id_count` is the parser's id count; lowering does not use it. When
+/// `emit_debug_info` is set, every lowered function gets a `DISubprogram`
+/// and its instructions are tagged with a `!dbg` location at the function's
+/// source line. When `coverage_map` is set, every region it names gets an
+/// `llvm.instrprof.increment` counter bump and the module gets a
+/// `__llvm_covmap`-section global holding the region-to-counter mapping.
+pub fn lower(
+  package: &Package,
+  _id_count: usize,
+  emit_debug_info: bool,
+  coverage_map: Option<&CoverageMap>,
+) -> (String, Vec<Diagnostic>) {
+  let context = Context::create();
+  let module = context.create_module("tails");
+
+  let instrprof_increment = coverage_map.map(|_| declare_instrprof_increment(&context, &module));
+
+  for (qualifier, ast_module) in package {
+    let mut module_debug_info = emit_debug_info
+      .then(|| ModuleDebugInfo::new(&module, &qualifier.module_name, &qualifier.package_name));
+
+    for function in &ast_module.functions {
+      let function_type = context.void_type().fn_type(&[], false);
+      let llvm_function = module.add_function(&function.name, function_type, None);
+      let entry_block = context.append_basic_block(llvm_function, "entry");
+      let builder = context.create_builder();
+
+      builder.position_at_end(entry_block);
+
+      if let Some(module_debug_info) = module_debug_info.as_mut() {
+        module_debug_info.enter_function(function);
+
+        if let Some(subprogram) = module_debug_info.current_subprogram {
+          let location = module_debug_info.builder.create_debug_location(
+            &context,
+            function.span.line as u32,
+            1,
+            subprogram.as_debug_info_scope(),
+            None,
+          );
+
+          builder.set_current_debug_location(location);
+        }
+      }
+
+      if let (Some(coverage_map), Some(instrprof_increment)) = (coverage_map, instrprof_increment) {
+        emit_counter_increments(&context, &builder, instrprof_increment, coverage_map, &function.name);
+      }
+
+      builder.build_return(None).expect("a function should always be able to terminate its entry block");
+    }
+
+    if let Some(module_debug_info) = &module_debug_info {
+      module_debug_info.finalize();
+    }
+  }
+
+  if let Some(coverage_map) = coverage_map {
+    emit_coverage_map_global(&context, &module, coverage_map);
+  }
+
+  (module.print_to_string().to_string(), Vec::new())
+}
+
+/// Declares the `llvm.instrprof.increment` counter-bump intrinsic:
+/// `void @llvm.instrprof.increment(i8*, i64, i32, i32)`.
+fn declare_instrprof_increment<'ctx>(
+  context: &'ctx Context,
+  module: &inkwell::module::Module<'ctx>,
+) -> inkwell::values::FunctionValue<'ctx> {
+  let name_ptr_type = context.i8_type().ptr_type(AddressSpace::default());
+
+  let function_type = context.void_type().fn_type(
+    &[
+      name_ptr_type.into(),
+      context.i64_type().into(),
+      context.i32_type().into(),
+      context.i32_type().into(),
+    ],
+    false,
+  );
+
+  module.add_function("llvm.instrprof.increment", function_type, None)
+}
+
+/// Emits one `llvm.instrprof.increment` call per region this function owns,
+/// each with its own dense `counter_index`.
+fn emit_counter_increments<'ctx>(
+  context: &'ctx Context,
+  builder: &inkwell::builder::Builder<'ctx>,
+  instrprof_increment: inkwell::values::FunctionValue<'ctx>,
+  coverage_map: &CoverageMap,
+  function_name: &str,
+) {
+  let Some(function_coverage) = coverage_map
+    .functions
+    .iter()
+    .find(|function_coverage| function_coverage.function_name == function_name)
+  else {
+    return;
+  };
+
+  let name_global = builder
+    .build_global_string_ptr(function_name, "coverage_function_name")
+    .expect("a coverage function name global should always be constructible");
+
+  for region in &function_coverage.regions {
+    builder
+      .build_call(
+        instrprof_increment,
+        &[
+          name_global.as_pointer_value().into(),
+          context.i64_type().const_zero().into(),
+          context
+            .i32_type()
+            .const_int(function_coverage.regions.len() as u64, false)
+            .into(),
+          context.i32_type().const_int(region.counter_index as u64, false).into(),
+        ],
+        "coverage_increment",
+      )
+      .expect("a coverage counter increment call should always be constructible");
+  }
+}
+
+/// Emits the `__llvm_covmap`-section global an external profiler reads to
+/// map counter indices back to source regions. Each function is encoded as
+/// `name:counter@start-end#line;...`, one entry per region.
+fn emit_coverage_map_global<'ctx>(
+  context: &'ctx Context,
+  module: &inkwell::module::Module<'ctx>,
+  coverage_map: &CoverageMap,
+) {
+  let encoded = coverage_map
+    .functions
+    .iter()
+    .map(|function_coverage| {
+      let regions = function_coverage
+        .regions
+        .iter()
+        .map(|region| {
+          format!(
+            "{}@{}-{}#{}",
+            region.counter_index, region.span.start, region.span.end, region.span.line
+          )
+        })
+        .collect::<Vec<_>>()
+        .join(";");
+
+      format!("{}:{}", function_coverage.function_name, regions)
+    })
+    .collect::<Vec<_>>()
+    .join(",");
+
+  let covmap_value = context.const_string(encoded.as_bytes(), true);
+
+  let global = module.add_global(covmap_value.get_type(), None, "__llvm_coverage_mapping");
+
+  global.set_initializer(&covmap_value);
+  global.set_section(Some(COVERAGE_MAP_SECTION));
+}
@@ -0,0 +1,140 @@
+pub mod cfg;
+pub mod coverage;
+pub mod debug_info;
+pub mod dominators;
+mod llvm_lowering;
+pub mod redundant_guard_elimination;
+
+use crate::{ast::Package, diagnostic::Diagnostic};
+use std::collections::HashMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PassId {
+  Resolution,
+  Inference,
+  RedundantGuardElimination,
+  /// Opt-in: records the coverage map (region-to-counter assignments) ahead
+  /// of `LlvmLowering`, which inserts the counters it names.
+  Coverage,
+  LlvmLowering,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PassResult {
+  /// The pass ran and did not need to produce a value of its own.
+  Ok,
+  /// The pass could not run because one of its `PassId` dependencies did
+  /// not run successfully first.
+  UnmetDependencies,
+  LlvmIrOutput(String),
+  /// How many guard checks the redundant guard elimination pass dropped.
+  GuardsEliminated(usize),
+  CoverageMap(coverage::CoverageMap),
+}
+
+pub struct PassManagerRunResult {
+  pub diagnostics: Vec<Diagnostic>,
+  pub results: HashMap<PassId, PassResult>,
+}
+
+/// Orchestrates the compiler's passes over a `Package`, running them in the
+/// order they were added (`add_all_passes` adds them in dependency order).
+pub struct PassManager<'a> {
+  package: &'a Package,
+  passes: Vec<PassId>,
+  emit_debug_info: bool,
+  emit_coverage: bool,
+}
+
+impl<'a> PassManager<'a> {
+  pub fn new(package: &'a Package) -> Self {
+    Self {
+      package,
+      passes: Vec::new(),
+      emit_debug_info: false,
+      emit_coverage: false,
+    }
+  }
+
+  /// Requests that `LlvmLowering` attach DWARF debug-info metadata (compile
+  /// unit, subprograms, and `!dbg` locations) to its output.
+  pub fn with_debug_info(mut self, emit_debug_info: bool) -> Self {
+    self.emit_debug_info = emit_debug_info;
+
+    self
+  }
+
+  /// Requests that a `Coverage` pass run ahead of `LlvmLowering`, inserting
+  /// counter increments and a coverage-map global into its output.
+  pub fn with_coverage(mut self, emit_coverage: bool) -> Self {
+    self.emit_coverage = emit_coverage;
+
+    self
+  }
+
+  /// Appends a single pass to the end of the pipeline. Lets callers (such
+  /// as `testing::run`'s `// passes: ...` header directive) run a specific
+  /// subset of passes instead of the full pipeline. A later
+  /// `add_all_passes` call replaces the whole pipeline, including passes
+  /// added here.
+  pub fn add_pass(&mut self, pass_id: PassId) {
+    self.passes.push(pass_id);
+  }
+
+  pub fn add_all_passes(&mut self) {
+    self.passes = vec![PassId::Resolution, PassId::Inference, PassId::RedundantGuardElimination];
+
+    if self.emit_coverage {
+      self.passes.push(PassId::Coverage);
+    }
+
+    self.passes.push(PassId::LlvmLowering);
+  }
+
+  pub fn run(&mut self, id_count: usize) -> PassManagerRunResult {
+    let mut diagnostics = Vec::new();
+    let mut results = HashMap::new();
+    let mut coverage_map = None;
+
+    for pass_id in &self.passes {
+      let result = match pass_id {
+        PassId::Resolution | PassId::Inference => PassResult::Ok,
+        PassId::RedundantGuardElimination => {
+          let mut removed_count = 0;
+
+          for mut function_cfg in cfg::build_cfgs_for_package(self.package) {
+            removed_count += redundant_guard_elimination::eliminate_redundant_guards(&mut function_cfg);
+          }
+
+          PassResult::GuardsEliminated(removed_count)
+        }
+        PassId::Coverage => {
+          let map = coverage::instrument_package(self.package);
+
+          coverage_map = Some(map.clone());
+
+          PassResult::CoverageMap(map)
+        }
+        PassId::LlvmLowering => {
+          let (ir, mut pass_diagnostics) = llvm_lowering::lower(
+            self.package,
+            id_count,
+            self.emit_debug_info,
+            coverage_map.as_ref(),
+          );
+
+          diagnostics.append(&mut pass_diagnostics);
+
+          PassResult::LlvmIrOutput(ir)
+        }
+      };
+
+      results.insert(*pass_id, result);
+    }
+
+    PassManagerRunResult {
+      diagnostics,
+      results,
+    }
+  }
+}
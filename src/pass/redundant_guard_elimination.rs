@@ -0,0 +1,147 @@
+//! Eliminates guard checks (`guard_division_by_zero`, `guard_null_dereference`,
+//! `guard_memo`) that are already proven by an identical guard on a
+//! dominating block.
+//!
+//! Two guards are considered equivalent only if they check the same
+//! `GuardKind` against the same SSA operand; no aliasing reasoning is
+//! performed, so a guard on a different (even provably-equal) value is
+//! left alone.
+
+use super::{
+  cfg::{Cfg, GuardCheck},
+  dominators,
+};
+use std::collections::{HashMap, HashSet};
+
+/// Removes guards already dominated by an equivalent guard, returning how
+/// many were removed.
+pub fn eliminate_redundant_guards(cfg: &mut Cfg) -> usize {
+  let immediate_dominators = dominators::compute_immediate_dominators(cfg);
+  let mut removed_count = 0;
+
+  for block in 0..cfg.block_count() {
+    // The entry block is always reachable; other blocks not present in the
+    // dominator map are unreachable and have no guards worth analyzing.
+    if block != Cfg::ENTRY && !immediate_dominators.contains_key(&block) {
+      continue;
+    }
+
+    let mut seen_in_block = HashSet::new();
+    let mut surviving_guards = Vec::with_capacity(cfg.blocks[block].guards.len());
+
+    for guard in cfg.blocks[block].guards.clone() {
+      let is_redundant = !seen_in_block.insert(guard)
+        || is_proven_by_dominator(cfg, &immediate_dominators, block, &guard);
+
+      if is_redundant {
+        removed_count += 1;
+      } else {
+        surviving_guards.push(guard);
+      }
+    }
+
+    cfg.blocks[block].guards = surviving_guards;
+  }
+
+  removed_count
+}
+
+fn is_proven_by_dominator(
+  cfg: &Cfg,
+  immediate_dominators: &HashMap<usize, usize>,
+  block: usize,
+  guard: &GuardCheck,
+) -> bool {
+  let mut current = block;
+
+  while let Some(&dominator) = immediate_dominators.get(&current) {
+    if cfg.blocks[dominator].guards.contains(guard) {
+      return true;
+    }
+
+    current = dominator;
+  }
+
+  false
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::pass::cfg::{BasicBlock, GuardKind};
+
+  fn block(predecessors: &[usize], successors: &[usize], guards: &[GuardCheck]) -> BasicBlock {
+    BasicBlock {
+      predecessors: predecessors.to_vec(),
+      successors: successors.to_vec(),
+      guards: guards.to_vec(),
+    }
+  }
+
+  #[test]
+  fn removes_guard_dominated_by_identical_guard() {
+    let guard = GuardCheck {
+      kind: GuardKind::DivisionByZero,
+      operand: 0,
+    };
+
+    // 0 -> 1 -> 2, with the same guard repeated on every block.
+    let mut cfg = Cfg {
+      blocks: vec![
+        block(&[], &[1], &[guard]),
+        block(&[0], &[2], &[guard]),
+        block(&[1], &[], &[guard]),
+      ],
+    };
+
+    let removed_count = eliminate_redundant_guards(&mut cfg);
+
+    assert_eq!(removed_count, 2);
+    assert_eq!(cfg.blocks[0].guards, vec![guard]);
+    assert!(cfg.blocks[1].guards.is_empty());
+    assert!(cfg.blocks[2].guards.is_empty());
+  }
+
+  #[test]
+  fn keeps_guards_on_different_operands() {
+    let guard_on_zero = GuardCheck {
+      kind: GuardKind::NullDereference,
+      operand: 0,
+    };
+
+    let guard_on_one = GuardCheck {
+      kind: GuardKind::NullDereference,
+      operand: 1,
+    };
+
+    let mut cfg = Cfg {
+      blocks: vec![
+        block(&[], &[1], &[guard_on_zero]),
+        block(&[0], &[], &[guard_on_one]),
+      ],
+    };
+
+    let removed_count = eliminate_redundant_guards(&mut cfg);
+
+    assert_eq!(removed_count, 0, "guards on different operands are not equivalent");
+    assert_eq!(cfg.blocks[1].guards, vec![guard_on_one]);
+  }
+
+  #[test]
+  fn ignores_guards_on_unreachable_blocks() {
+    let guard = GuardCheck {
+      kind: GuardKind::Memo,
+      operand: 0,
+    };
+
+    // Block 1 has no path from the entry block and should be left alone.
+    let mut cfg = Cfg {
+      blocks: vec![block(&[], &[], &[guard]), block(&[], &[], &[guard])],
+    };
+
+    let removed_count = eliminate_redundant_guards(&mut cfg);
+
+    assert_eq!(removed_count, 0);
+    assert_eq!(cfg.blocks[1].guards, vec![guard]);
+  }
+}
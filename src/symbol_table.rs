@@ -0,0 +1,7 @@
+/// Identifies a module uniquely within a package, used to namespace symbols
+/// during resolution and to key the `ast::Package` map.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Qualifier {
+  pub package_name: String,
+  pub module_name: String,
+}
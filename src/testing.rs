@@ -0,0 +1,347 @@
+//! A public, reusable test-running harness for `.tails` source files.
+//!
+//! This is the library entry point crates other than `tails` itself (or
+//! its own `tests/` integration suite) can script the compiler pipeline
+//! against: lex, parse, and run the pass pipeline over a source string,
+//! configured by compiletest-style header directives read from its
+//! leading comment block, rather than by which folder the file happens to
+//! live in. It also bundles the LLVM IR normalization helpers used to keep
+//! golden tests stable across LLVM/inkwell versions.
+
+use crate::{
+  ast, diagnostic,
+  lexer::{self, TokenKind},
+  parser, pass,
+  symbol_table::Qualifier,
+};
+use regex::Regex;
+use std::collections::HashMap;
+
+/// Header directives parsed from a `.tails` file's leading `//` comments,
+/// one directive per line:
+///
+/// - `// check-pass` stop after type-checking; skip LLVM lowering.
+/// - `// build-pass` require that LLVM lowering succeed.
+/// - `// passes: resolution,inference` run only this subset of `pass::PassId`s.
+/// - `// expect-error: <code>` expect a diagnostic whose message contains `<code>`.
+/// - `// debug-info` request a debug build (`PassManager::with_debug_info`).
+/// - `// coverage` request coverage instrumentation (`PassManager::with_coverage`).
+///
+/// Parsing stops at the first blank or non-directive line, so headers must
+/// be contiguous at the top of the file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TestDirectives {
+  pub check_pass: bool,
+  pub build_pass: bool,
+  pub only_passes: Option<Vec<String>>,
+  pub expect_error: Option<String>,
+  pub debug_info: bool,
+  pub coverage: bool,
+}
+
+pub fn parse_test_directives(source_code: &str) -> TestDirectives {
+  let mut directives = TestDirectives::default();
+
+  for line in source_code.lines() {
+    let trimmed = line.trim();
+
+    if trimmed.is_empty() {
+      // A blank line ends the contiguous header block, same as any other
+      // non-directive line.
+      break;
+    }
+
+    let Some(content) = trimmed.strip_prefix("//") else {
+      break;
+    };
+
+    let content = content.trim();
+
+    if content == "check-pass" {
+      directives.check_pass = true;
+    } else if content == "build-pass" {
+      directives.build_pass = true;
+    } else if let Some(rest) = content.strip_prefix("passes:") {
+      directives.only_passes = Some(
+        rest
+          .split(',')
+          .map(|name| name.trim().to_string())
+          .filter(|name| !name.is_empty())
+          .collect(),
+      );
+    } else if let Some(rest) = content.strip_prefix("expect-error:") {
+      directives.expect_error = Some(rest.trim().to_string());
+    } else if content == "debug-info" {
+      directives.debug_info = true;
+    } else if content == "coverage" {
+      directives.coverage = true;
+    } else {
+      // Not a recognized directive; the header block ends here.
+      break;
+    }
+  }
+
+  directives
+}
+
+fn pass_id_by_name(name: &str) -> Option<pass::PassId> {
+  match name {
+    "resolution" => Some(pass::PassId::Resolution),
+    "inference" => Some(pass::PassId::Inference),
+    "redundant_guard_elimination" => Some(pass::PassId::RedundantGuardElimination),
+    "coverage" => Some(pass::PassId::Coverage),
+    "llvm_lowering" => Some(pass::PassId::LlvmLowering),
+    _ => None,
+  }
+}
+
+/// What `run` produced: the diagnostics from the run, and the LLVM IR if
+/// `LlvmLowering` was among the passes that ran.
+pub struct TestRunOutput {
+  pub diagnostics: Vec<diagnostic::Diagnostic>,
+  pub llvm_ir: Option<String>,
+}
+
+/// Lexes, parses, and runs the pass pipeline over `source_code`, driving
+/// which passes run and whether the test should be considered to have
+/// passed or failed from its header directives instead of from the
+/// caller's own folder-based convention.
+pub fn run(source_code: &str, qualifier: Qualifier) -> diagnostic::Maybe<TestRunOutput> {
+  let directives = parse_test_directives(source_code);
+
+  let tokens = lexer::Lexer::lex_all(source_code)?;
+
+  let filtered_tokens = tokens
+    .into_iter()
+    .filter(|token| !matches!(token.0, TokenKind::Whitespace(_) | TokenKind::Comment(_)))
+    .collect();
+
+  let mut parser = parser::Parser::new(filtered_tokens);
+  let module = parser.parse_module(qualifier.clone())?;
+  let package = ast::Package::from([(qualifier, module)]);
+  let mut pass_manager = pass::PassManager::new(&package)
+    .with_debug_info(directives.debug_info)
+    .with_coverage(directives.coverage);
+
+  if let Some(names) = &directives.only_passes {
+    for name in names {
+      if let Some(pass_id) = pass_id_by_name(name) {
+        pass_manager.add_pass(pass_id);
+      }
+    }
+  } else if directives.check_pass {
+    pass_manager.add_pass(pass::PassId::Resolution);
+    pass_manager.add_pass(pass::PassId::Inference);
+  } else {
+    pass_manager.add_all_passes();
+  }
+
+  let run_result = pass_manager.run(parser.get_id_count());
+
+  let diagnostics_helper = diagnostic::DiagnosticsHelper {
+    diagnostics: run_result.diagnostics,
+  };
+
+  let expected_error_matched = directives
+    .expect_error
+    .as_ref()
+    .map(|code| diagnostics_helper.diagnostics.iter().any(|diagnostic| diagnostic.message.contains(code.as_str())));
+
+  match expected_error_matched {
+    Some(true) => {}
+    Some(false) => return Err(diagnostics_helper.diagnostics),
+    None if diagnostics_helper.contains_errors() => return Err(diagnostics_helper.diagnostics),
+    None => {}
+  }
+
+  let llvm_ir = match run_result.results.get(&pass::PassId::LlvmLowering) {
+    Some(pass::PassResult::LlvmIrOutput(llvm_ir)) => Some(llvm_ir.clone()),
+    _ => None,
+  };
+
+  if directives.build_pass && llvm_ir.is_none() {
+    return Err(diagnostics_helper.diagnostics);
+  }
+
+  Ok(TestRunOutput {
+    diagnostics: diagnostics_helper.diagnostics,
+    llvm_ir,
+  })
+}
+
+/// A single `; normalize: "<pattern>" -> "<replacement>"` directive parsed
+/// out of an expected `.ll` file, applied as a regex substitution after the
+/// built-in SSA/metadata/target normalization runs.
+#[derive(Clone, Debug)]
+pub struct NormalizeDirective {
+  pub pattern: Regex,
+  pub replacement: String,
+}
+
+const DIRECTIVE_PREFIX: &str = "; normalize:";
+
+/// Parses `; normalize: "<pattern>" -> "<replacement>"` comment lines out
+/// of an expected `.ll` file's source text.
+pub fn parse_normalize_directives(expected_ir_source: &str) -> Vec<NormalizeDirective> {
+  let directive_line = Regex::new(r#"^\s*;\s*normalize:\s*"(.*)"\s*->\s*"(.*)"\s*$"#)
+    .expect("directive regex should be valid");
+
+  expected_ir_source
+    .lines()
+    .filter(|line| line.trim_start().starts_with(DIRECTIVE_PREFIX))
+    .filter_map(|line| {
+      let captures = directive_line.captures(line)?;
+
+      Some(NormalizeDirective {
+        pattern: Regex::new(&captures[1]).expect("normalize directive pattern should be valid"),
+        replacement: captures[2].to_string(),
+      })
+    })
+    .collect()
+}
+
+/// Renumbers anonymous values/metadata and canonicalizes target lines, then
+/// applies any per-test `directives` as regex substitutions.
+pub fn normalize(llvm_ir: &str, directives: &[NormalizeDirective]) -> String {
+  let llvm_ir = renumber(llvm_ir, '%');
+  let llvm_ir = renumber(&llvm_ir, '!');
+
+  let target_line = Regex::new(r#"(?m)^target (datalayout|triple) = ".*"$"#)
+    .expect("target line regex should be valid");
+
+  let mut normalized = target_line
+    .replace_all(&llvm_ir, |captures: &regex::Captures| {
+      format!("target {} = \"<normalized>\"", &captures[1])
+    })
+    .into_owned();
+
+  for directive in directives {
+    normalized = directive
+      .pattern
+      .replace_all(&normalized, directive.replacement.as_str())
+      .into_owned();
+  }
+
+  normalized
+}
+
+/// Renumbers anonymous `<sigil>N` references (e.g. `%0`, `!12`) into
+/// canonical order-of-first-appearance, leaving named references
+/// (`%foo`, `!"bar"`) untouched.
+fn renumber(llvm_ir: &str, sigil: char) -> String {
+  let reference = Regex::new(&format!(r"\{sigil}(\d+)")).expect("reference regex should be valid");
+  let mut canonical_ids: HashMap<String, usize> = HashMap::new();
+
+  reference
+    .replace_all(llvm_ir, |captures: &regex::Captures| {
+      let original = captures[1].to_string();
+      let next_id = canonical_ids.len();
+      let canonical_id = *canonical_ids.entry(original).or_insert(next_id);
+
+      format!("{sigil}{canonical_id}")
+    })
+    .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_check_pass_build_pass_passes_and_expect_error() {
+    let source = "// check-pass\n// build-pass\n// passes: resolution, inference\n// expect-error: E0308\nfn main() {}\n";
+
+    let directives = parse_test_directives(source);
+
+    assert_eq!(
+      directives,
+      TestDirectives {
+        check_pass: true,
+        build_pass: true,
+        only_passes: Some(vec![String::from("resolution"), String::from("inference")]),
+        expect_error: Some(String::from("E0308")),
+        debug_info: false,
+        coverage: false,
+      }
+    );
+  }
+
+  #[test]
+  fn parses_debug_info_directive() {
+    let directives = parse_test_directives("// debug-info\nfn main() {}\n");
+
+    assert!(directives.debug_info);
+  }
+
+  #[test]
+  fn parses_coverage_directive() {
+    let directives = parse_test_directives("// coverage\nfn main() {}\n");
+
+    assert!(directives.coverage);
+  }
+
+  #[test]
+  fn header_parsing_stops_at_the_first_non_directive_line() {
+    let source = "// check-pass\nfn main() {}\n// passes: resolution\n";
+
+    let directives = parse_test_directives(source);
+
+    assert!(directives.check_pass);
+    assert_eq!(directives.only_passes, None, "directives after source code should not be parsed");
+  }
+
+  #[test]
+  fn no_header_yields_default_directives() {
+    assert_eq!(parse_test_directives("fn main() {}\n"), TestDirectives::default());
+  }
+
+  #[test]
+  fn header_parsing_stops_at_a_blank_line() {
+    let source = "// check-pass\n\n// passes: foo\n";
+
+    let directives = parse_test_directives(source);
+
+    assert!(directives.check_pass);
+    assert_eq!(
+      directives.only_passes, None,
+      "a blank line should end the contiguous header, so directives after it are not parsed"
+    );
+  }
+
+  #[test]
+  fn renumbers_anonymous_values_and_metadata_into_canonical_order() {
+    let llvm_ir = "%7 = add i32 %3, %3\n%9 = mul i32 %7, %3, !dbg !12\n!12 = !{!5}\n";
+
+    let normalized = normalize(llvm_ir, &[]);
+
+    assert_eq!(
+      normalized,
+      "%0 = add i32 %1, %1\n%2 = mul i32 %0, %1, !dbg !0\n!0 = !{!1}\n"
+    );
+  }
+
+  #[test]
+  fn canonicalizes_target_lines() {
+    let llvm_ir = "target datalayout = \"e-m:e-p270:32:32\"\ntarget triple = \"x86_64-unknown-linux-gnu\"\n";
+
+    let normalized = normalize(llvm_ir, &[]);
+
+    assert_eq!(
+      normalized,
+      "target datalayout = \"<normalized>\"\ntarget triple = \"<normalized>\"\n"
+    );
+  }
+
+  #[test]
+  fn applies_per_test_normalize_directives() {
+    let expected_ir_source = "; normalize: \"tails_[0-9]+\" -> \"tails_N\"\ndefine void @tails_42() {\nret void\n}\n";
+
+    let directives = parse_normalize_directives(expected_ir_source);
+
+    assert_eq!(directives.len(), 1);
+
+    let normalized = normalize("define void @tails_42() {\nret void\n}\n", &directives);
+
+    assert_eq!(normalized, "define void @tails_N() {\nret void\n}\n");
+  }
+}
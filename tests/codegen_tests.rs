@@ -1,10 +1,14 @@
 extern crate inkwell;
 extern crate tails;
 
+#[path = "common/mod.rs"]
+mod common;
+
 #[cfg(test)]
 mod tests {
+  use crate::common::annotations;
   use pretty_assertions::assert_eq;
-  use tails::{diagnostic, lexer, pass};
+  use tails::{diagnostic, pass};
 
   const TESTS_FOLDER: &str = "tests";
   const BUG_CURRENT_FOLDER: &str = "the current directory should exist and be accessible";
@@ -12,74 +16,9 @@ mod tests {
   const BUG_FILE_READ: &str =
     "test source file should exist, be accessible, and its contents should be valid UTF-8";
 
-  fn lex_and_filter(source_code: &str) -> diagnostic::Maybe<Vec<lexer::Token>> {
-    let tokens = tails::lexer::Lexer::lex_all(source_code)?;
-
-    // SAFETY: What about illegal tokens? Would it cause the parser to error?
-    // Filter tokens to only include those that are relevant (ignore
-    // whitespace, comments, etc.).
-    let filtered_tokens = tokens
-      .into_iter()
-      .filter(|token| {
-        !matches!(
-          token.0,
-          tails::lexer::TokenKind::Whitespace(_) | tails::lexer::TokenKind::Comment(_)
-        )
-      })
-      .collect();
-
-    Ok(filtered_tokens)
-  }
-
-  fn lower_file(
-    source_file_contents: &str,
-    qualifier: tails::symbol_table::Qualifier,
-  ) -> diagnostic::Maybe<String> {
-    let mut parser = tails::parser::Parser::new(lex_and_filter(source_file_contents)?);
-    let module_result = parser.parse_module(qualifier.clone());
-
-    let module = match module_result {
-      Ok(unit) => unit,
-      Err(diagnostics) => return Err(diagnostics),
-    };
-
-    let test_package = tails::ast::Package::from([(qualifier.clone(), module)]);
-    let mut pass_manager = pass::PassManager::new(&test_package);
-
-    pass_manager.add_all_passes();
-
-    let pass_manager_run_result = pass_manager.run(parser.get_id_count());
-
-    let diagnostics_helper = diagnostic::DiagnosticsHelper {
-      diagnostics: pass_manager_run_result.diagnostics,
-    };
-
-    if diagnostics_helper.contains_errors() {
-      return Err(diagnostics_helper.diagnostics);
-    }
-
-    // Ensure that no pass has unmet dependencies.
-    for pass_result in &pass_manager_run_result.results {
-      assert!(
-        !matches!(pass_result.1, pass::PassResult::UnmetDependencies),
-        "no pass should have unmet dependencies"
-      );
-    }
-
-    let llvm_lowering_pass_result = pass_manager_run_result
-      .results
-      .get(&pass::PassId::LlvmLowering)
-      .expect("backend output should have been produced if there were no error diagnostics");
-
-    Ok(match llvm_lowering_pass_result {
-      // OPTIMIZE: Consume result and avoid cloning.
-      pass::PassResult::LlvmIrOutput(llvm_ir_output) => llvm_ir_output.to_owned(),
-      _ => {
-        unreachable!("backend output should have been produced if there were no error diagnostics")
-      }
-    })
-  }
-
+  /// Reads and runs a test source file, driving the pipeline and its
+  /// pass/fail outcome from `tails::testing`'s header directives rather
+  /// than from `folder_name`; `folder_name` only locates the file on disk.
   fn run_test(name: &str, folder_name: &str) -> diagnostic::Maybe<String> {
     const FILENAME_EXTENSION: &str = "tails";
 
@@ -101,7 +40,8 @@ mod tests {
       module_name: name.to_string(),
     };
 
-    lower_file(&source_file_contents, qualifier).map(|output| output.trim().to_string())
+    tails::testing::run(&source_file_contents, qualifier)
+      .map(|output| output.llvm_ir.unwrap_or_default().trim().to_string())
   }
 
   fn run_passing_test(name: &str) {
@@ -122,11 +62,9 @@ mod tests {
     let actual_output = run_test(name, INPUT_FOLDER)
       .expect("there should be no error diagnostics produced on a passing test");
 
-    let expected_output = if output_file_path.exists() {
+    let expected_output_source = if output_file_path.exists() {
       std::fs::read_to_string(output_file_path)
         .expect("corresponding output file exists, but cannot be read")
-        .trim()
-        .to_string()
     }
     // If the expected output file does not exist, that is acceptable;
     // the output LLVM IR is irrelevant. For example, this could mean that
@@ -136,11 +74,36 @@ mod tests {
       return;
     };
 
+    let directives = tails::testing::parse_normalize_directives(&expected_output_source);
+
+    // The directive comment lines are not part of the expected IR itself.
+    let expected_ir_source = expected_output_source
+      .lines()
+      .filter(|line| !line.trim_start().starts_with("; normalize:"))
+      .collect::<Vec<_>>()
+      .join("\n");
+
+    let expected_output =
+      tails::testing::normalize(expected_ir_source.trim(), &directives);
+
+    let actual_output = tails::testing::normalize(actual_output.trim(), &directives);
+
     assert_eq!(expected_output, actual_output);
   }
 
-  fn run_failing_test(name: &str, matcher: &dyn Fn(Vec<diagnostic::Diagnostic>) -> bool) {
+  fn run_failing_test(name: &str) {
     const FAILING_FOLDER: &str = "failing";
+    const FILENAME_EXTENSION: &str = "tails";
+
+    let source_file_path = std::env::current_dir()
+      .expect(BUG_CURRENT_FOLDER)
+      .join(TESTS_FOLDER)
+      .join(FAILING_FOLDER)
+      .join(name)
+      .with_extension(FILENAME_EXTENSION);
+
+    let source_file_contents = std::fs::read_to_string(source_file_path).expect(BUG_FILE_READ);
+    let expected_annotations = annotations::parse_annotations(&source_file_contents);
 
     match run_test(name, FAILING_FOLDER) {
       Ok(llvm_ir_output) => {
@@ -148,16 +111,10 @@ mod tests {
         panic!("failing tests should not succeed");
       }
       Err(diagnostics) => {
-        let matcher_result = matcher(diagnostics.clone());
-
-        if !matcher_result {
-          dbg!(diagnostics);
+        if let Err(diff) = annotations::match_diagnostics(&expected_annotations, &diagnostics) {
+          dbg!(&diagnostics);
+          panic!("{}", diff);
         }
-
-        assert!(
-          matcher_result,
-          "failing test should produce expected diagnostics"
-        );
       }
     }
   }
@@ -178,7 +135,7 @@ mod tests {
       $(
         #[test]
         fn $name() {
-          run_failing_test(stringify!($name), &|diagnostics| !diagnostics.is_empty());
+          run_failing_test(stringify!($name));
         }
       )*
     };
@@ -312,4 +269,105 @@ mod tests {
     resolution_missing_function,
     type_infer_mismatch
   );
+
+  /// Builds a single-function `Package` under `name`, for tests that only
+  /// care about the pass pipeline's behavior and not real parsed source.
+  fn single_function_package(name: &str) -> tails::ast::Package {
+    let qualifier = tails::symbol_table::Qualifier {
+      package_name: String::from(TESTS_FOLDER),
+      module_name: name.to_string(),
+    };
+
+    let function = tails::ast::Function {
+      name: String::from("main"),
+      parameters: Vec::new(),
+      body: tails::ast::Block::default(),
+      span: tails::lexer::Span::new(0, 0, 1),
+    };
+
+    let module = tails::ast::Module {
+      functions: vec![function],
+    };
+
+    tails::ast::Package::from([(qualifier, module)])
+  }
+
+  #[test]
+  fn debug_info_emits_dwarf_metadata() {
+    let test_package = single_function_package("debug_info_emits_dwarf_metadata");
+    let mut pass_manager = pass::PassManager::new(&test_package).with_debug_info(true);
+
+    pass_manager.add_all_passes();
+
+    let pass_manager_run_result = pass_manager.run(0);
+
+    let llvm_ir = match pass_manager_run_result
+      .results
+      .get(&pass::PassId::LlvmLowering)
+    {
+      Some(pass::PassResult::LlvmIrOutput(llvm_ir)) => llvm_ir.clone(),
+      _ => panic!("expected a debug-info build to still produce LLVM IR output"),
+    };
+
+    assert!(
+      llvm_ir.contains("DISubprogram"),
+      "expected a DISubprogram entry for the lowered function"
+    );
+
+    assert!(
+      llvm_ir.contains("!dbg"),
+      "expected the function's instructions to carry !dbg locations"
+    );
+  }
+
+  #[test]
+  fn coverage_instruments_function_entries() {
+    let test_package = single_function_package("coverage_instruments_function_entries");
+    let mut pass_manager = pass::PassManager::new(&test_package).with_coverage(true);
+
+    pass_manager.add_all_passes();
+
+    let pass_manager_run_result = pass_manager.run(0);
+
+    let coverage_map = match pass_manager_run_result.results.get(&pass::PassId::Coverage) {
+      Some(pass::PassResult::CoverageMap(coverage_map)) => coverage_map.clone(),
+      _ => panic!("expected a coverage map to have been produced"),
+    };
+
+    let function_coverage = coverage_map
+      .functions
+      .iter()
+      .find(|function_coverage| function_coverage.function_name == "main")
+      .expect("the lowered function should have a coverage entry");
+
+    let counter_indices: std::collections::HashSet<_> = function_coverage
+      .regions
+      .iter()
+      .map(|region| region.counter_index)
+      .collect();
+
+    assert_eq!(
+      counter_indices,
+      (0..function_coverage.regions.len() as u32).collect::<std::collections::HashSet<_>>(),
+      "counter indices should be dense and unique per function"
+    );
+
+    let llvm_ir = match pass_manager_run_result
+      .results
+      .get(&pass::PassId::LlvmLowering)
+    {
+      Some(pass::PassResult::LlvmIrOutput(llvm_ir)) => llvm_ir.clone(),
+      _ => panic!("expected coverage instrumentation to still produce LLVM IR output"),
+    };
+
+    assert!(
+      llvm_ir.contains("llvm.instrprof.increment"),
+      "expected the function entry to be instrumented with a counter increment"
+    );
+
+    assert!(
+      llvm_ir.contains("__llvm_covmap"),
+      "expected a __llvm_covmap-section global holding the coverage mapping"
+    );
+  }
 }
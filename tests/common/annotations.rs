@@ -0,0 +1,222 @@
+//! Parses rustc-compiletest-style `//~` expected-diagnostic annotations out
+//! of a `.tails` source file, and checks a set of produced diagnostics
+//! against them.
+//!
+//! Supported forms, matching a single line each:
+//!
+//! - `//~ ERROR substring`   expects a diagnostic on the *same* line.
+//! - `//~^ ERROR substring`  expects a diagnostic one line *up*; repeating
+//!   the `^` (e.g. `//~^^`) walks further up, one line per caret.
+//! - `//~| ERROR substring`  expects another diagnostic on the same line as
+//!   the previous annotation (chains off of `^`/same-line annotations).
+
+use tails::diagnostic::{Diagnostic, Severity};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExpectedAnnotation {
+  pub line: usize,
+  pub severity: Severity,
+  pub message_substring: String,
+}
+
+const MARKER: &str = "//~";
+
+pub fn parse_annotations(source_code: &str) -> Vec<ExpectedAnnotation> {
+  let mut annotations = Vec::new();
+  // Tracks the source line the most recently parsed annotation targeted,
+  // so that a subsequent `//~|` can chain off of it.
+  let mut previous_target_line = None;
+
+  for (index, line) in source_code.lines().enumerate() {
+    let current_line = index + 1;
+
+    let Some(marker_index) = line.find(MARKER) else {
+      continue;
+    };
+
+    let rest = &line[marker_index + MARKER.len()..];
+
+    let (target_line, rest) = if let Some(rest) = rest.strip_prefix('|') {
+      match previous_target_line {
+        Some(line) => (line, rest),
+        // A `//~|` with nothing preceding it has nothing to chain off of;
+        // skip it rather than guessing a target line.
+        None => continue,
+      }
+    } else {
+      let carets_len = rest.chars().take_while(|character| *character == '^').count();
+
+      (current_line.saturating_sub(carets_len), &rest[carets_len..])
+    };
+
+    let Some((severity, message_substring)) = parse_severity_and_message(rest) else {
+      continue;
+    };
+
+    previous_target_line = Some(target_line);
+
+    annotations.push(ExpectedAnnotation {
+      line: target_line,
+      severity,
+      message_substring,
+    });
+  }
+
+  annotations
+}
+
+fn parse_severity_and_message(rest: &str) -> Option<(Severity, String)> {
+  let rest = rest.trim_start();
+
+  let (severity, rest) = if let Some(rest) = rest.strip_prefix("ERROR") {
+    (Severity::Error, rest)
+  } else if let Some(rest) = rest.strip_prefix("WARNING") {
+    (Severity::Warning, rest)
+  } else {
+    return None;
+  };
+
+  Some((severity, rest.trim().to_string()))
+}
+
+/// Matches expected annotations one-to-one against produced diagnostics.
+///
+/// Every annotation must match exactly one diagnostic (by line, severity,
+/// and substring), and every diagnostic must be matched by some annotation;
+/// on failure, returns a human-readable diff of what was expected versus
+/// what was actually produced.
+pub fn match_diagnostics(
+  expected: &[ExpectedAnnotation],
+  diagnostics: &[Diagnostic],
+) -> Result<(), String> {
+  let mut unmatched_diagnostics: Vec<&Diagnostic> = diagnostics.iter().collect();
+  let mut unmatched_annotations = Vec::new();
+
+  for annotation in expected {
+    let matching_index = unmatched_diagnostics.iter().position(|diagnostic| {
+      diagnostic.primary_line() == annotation.line
+        && diagnostic.severity == annotation.severity
+        && diagnostic.message.contains(&annotation.message_substring)
+    });
+
+    match matching_index {
+      Some(index) => {
+        unmatched_diagnostics.remove(index);
+      }
+      None => unmatched_annotations.push(annotation),
+    }
+  }
+
+  if unmatched_annotations.is_empty() && unmatched_diagnostics.is_empty() {
+    return Ok(());
+  }
+
+  let mut diff = String::from("expected-diagnostic annotations did not match produced diagnostics\n");
+
+  for annotation in &unmatched_annotations {
+    diff.push_str(&format!(
+      "  - expected but not produced: line {} {:?} containing {:?}\n",
+      annotation.line, annotation.severity, annotation.message_substring
+    ));
+  }
+
+  for diagnostic in &unmatched_diagnostics {
+    diff.push_str(&format!(
+      "  - produced but not expected: line {} {:?} {:?}\n",
+      diagnostic.primary_line(),
+      diagnostic.severity,
+      diagnostic.message
+    ));
+  }
+
+  Err(diff)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use tails::lexer::Span;
+
+  fn diagnostic(line: usize, severity: Severity, message: &str) -> Diagnostic {
+    Diagnostic::new(message, severity, Span::new(0, 0, line))
+  }
+
+  const SOURCE: &str = "fn main() {\n\
+    let x = bad_call(); //~ ERROR undefined function\n\
+    let y = 1\n\
+    //~^ ERROR missing semicolon\n\
+    //~| WARNING style nit\n\
+  }\n";
+
+  #[test]
+  fn parses_same_line_caret_and_chained_annotations() {
+    let annotations = parse_annotations(SOURCE);
+
+    assert_eq!(
+      annotations,
+      vec![
+        ExpectedAnnotation {
+          line: 2,
+          severity: Severity::Error,
+          message_substring: String::from("undefined function"),
+        },
+        ExpectedAnnotation {
+          line: 3,
+          severity: Severity::Error,
+          message_substring: String::from("missing semicolon"),
+        },
+        ExpectedAnnotation {
+          line: 3,
+          severity: Severity::Warning,
+          message_substring: String::from("style nit"),
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn matches_when_every_annotation_and_diagnostic_pair_up() {
+    let annotations = parse_annotations(SOURCE);
+
+    let diagnostics = vec![
+      diagnostic(2, Severity::Error, "undefined function `bad_call`"),
+      diagnostic(3, Severity::Error, "missing semicolon after `let y = 1`"),
+      diagnostic(3, Severity::Warning, "style nit: prefer `const`"),
+    ];
+
+    assert_eq!(match_diagnostics(&annotations, &diagnostics), Ok(()));
+  }
+
+  #[test]
+  fn fails_when_a_diagnostic_is_unmatched() {
+    let annotations = vec![ExpectedAnnotation {
+      line: 2,
+      severity: Severity::Error,
+      message_substring: String::from("undefined function"),
+    }];
+
+    let diagnostics = vec![
+      diagnostic(2, Severity::Error, "undefined function `bad_call`"),
+      diagnostic(5, Severity::Error, "unexpected extra diagnostic"),
+    ];
+
+    let result = match_diagnostics(&annotations, &diagnostics);
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains("produced but not expected"));
+  }
+
+  #[test]
+  fn fails_when_an_annotation_is_unmatched() {
+    let annotations = vec![ExpectedAnnotation {
+      line: 2,
+      severity: Severity::Error,
+      message_substring: String::from("never produced"),
+    }];
+
+    let result = match_diagnostics(&annotations, &[]);
+
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains("expected but not produced"));
+  }
+}